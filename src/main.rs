@@ -1,7 +1,7 @@
 use gl::types::*;
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::ptr;
-use std::str;
 use glutin::window::WindowBuilder;
 use glutin::ContextBuilder;
 use std::io::{self, Write};
@@ -10,23 +10,6 @@ use clap::{App, Arg};
 // Universal approach to OpenGL error handling
 // Wrap every all into gl_safe!(...) instead of unsafe { ... }
 macro_rules! gl_safe {
-    (gl::CompileShader(_shader:expr), $step_name:expr) => {{
-        let $shader = _shader; // compute expression once
-        let result = unsafe { gl::CompileShader($shader) };
-        
-        // Check for compilation errors
-        let mut success = gl::FALSE as gl::types::GLint;
-        unsafe { gl::GetShaderiv($shader, gl::COMPILE_STATUS, &mut success);}
-        if success != gl::TRUE as gl::types::GLint {
-            let mut len = 0;
-            unsafe { gl::GetShaderiv($shader, gl::INFO_LOG_LENGTH, &mut len); }
-            let mut buffer = vec![0u8; len as usize];
-            unsafe { gl::GetShaderInfoLog($shader, len, ptr::null_mut(), buffer.as_mut_ptr() as *mut GLchar); }
-            
-            panic!("Shader compilation failed at \"{}\": {}. Check the shader source code for errors.", $step_name, str::from_utf8(&buffer).unwrap());
-        }
-        result
-    }};
     (gl::load_with($func:expr), $step_name:expr) => {{
         let result = gl::load_with($func); // safe call
 
@@ -49,12 +32,612 @@ macro_rules! gl_safe {
     }};
 }
 
-fn compile_shader(src: &str, ty: GLenum) -> GLuint {
-    let shader = gl_safe!(gl::CreateShader(ty), "create shader: initialize a new shader object. Ensure the shader type is correct.");
-    let c_str = CString::new(src.as_bytes()).unwrap();
-    gl_safe!(gl::ShaderSource(shader, 1, &c_str.as_ptr(), ptr::null()), "set shader source: provide source code to shader. Ensure the source is valid GLSL.");
-    gl_safe!(gl::CompileShader(shader), "compile shader: compile the shader source code.");
-    shader
+// Selects which GLSL version/profile shader sources are compiled against,
+// so the same renderer can target desktop GL (glsl3) as well as embedded
+// and mobile GL contexts that only expose GLSL ES 1.00 (gles2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShaderVersion {
+    Glsl3,
+    Gles2,
+}
+
+impl std::str::FromStr for ShaderVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "glsl3" => Ok(ShaderVersion::Glsl3),
+            "gles2" => Ok(ShaderVersion::Gles2),
+            other => Err(format!("Unknown shader profile '{}'. Expected 'glsl3' or 'gles2'.", other)),
+        }
+    }
+}
+
+impl ShaderVersion {
+    // The `#version` directive to prepend when the source doesn't already declare one.
+    fn version_header(self) -> &'static str {
+        match self {
+            ShaderVersion::Glsl3 => "#version 330 core\n",
+            ShaderVersion::Gles2 => "#version 100\n#define GLES2_RENDERER\n",
+        }
+    }
+
+    // Extra compatibility defines needed to keep shader bodies portable across profiles.
+    fn compat_preamble(self) -> &'static str {
+        match self {
+            ShaderVersion::Glsl3 => "",
+            ShaderVersion::Gles2 => "precision mediump float;\n#define FragColor gl_FragColor\n",
+        }
+    }
+
+    fn vertex_source(self) -> &'static str {
+        match self {
+            ShaderVersion::Glsl3 => "#version 330 core\nlayout (location = 0) in vec3 aPos;\nvoid main() {\ngl_Position = vec4(aPos, 1.0);\n}",
+            ShaderVersion::Gles2 => "#version 100\nattribute vec3 aPos;\nvoid main() {\ngl_Position = vec4(aPos, 1.0);\n}",
+        }
+    }
+}
+
+// Injects the profile's version header (and, for gles2, its compatibility
+// preamble) unless the shader source already declares its own `#version`.
+fn prepare_fragment_source(src: &str, profile: ShaderVersion) -> String {
+    if src.trim_start().starts_with("#version") {
+        return src.to_string();
+    }
+
+    let body = match profile {
+        // GLSL ES 1.00 has no user-declared fragment outputs and won't let
+        // a shader redeclare the builtin `gl_FragColor`, so the `#define
+        // FragColor gl_FragColor` alias below would also rewrite (and
+        // break) the declaration itself unless it's dropped first.
+        ShaderVersion::Gles2 => strip_frag_color_declaration(src),
+        ShaderVersion::Glsl3 => src.to_string(),
+    };
+
+    let mut prepared = String::with_capacity(body.len() + 64);
+    prepared.push_str(profile.version_header());
+    prepared.push_str(profile.compat_preamble());
+    prepared.push_str(&body);
+    prepared
+}
+
+// Removes a top-level `out vec4 FragColor;` declaration line so the gles2
+// compat preamble can alias `FragColor` to `gl_FragColor` without also
+// macro-rewriting its own declaration.
+fn strip_frag_color_declaration(src: &str) -> String {
+    src.lines()
+        .filter(|line| line.trim() != "out vec4 FragColor;")
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Carries a shader/program diagnostic instead of panicking, so callers (the
+// `--watch` loop in particular) can keep the last known-good program on-screen.
+#[derive(Debug)]
+enum ShaderError {
+    Compile { stage: &'static str, log: String },
+    Link { log: String },
+}
+
+impl std::fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderError::Compile { stage, log } => write!(f, "{} shader compilation failed: {}", stage, log),
+            ShaderError::Link { log } => write!(f, "program linking failed: {}", log),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+// Shared by the compile and link paths so the info-log retrieval dance
+// (query length, allocate, fetch) only lives in one place.
+fn info_log(get_len: impl FnOnce(&mut GLint), get_log: impl FnOnce(GLint, *mut GLchar)) -> String {
+    let mut len = 0;
+    get_len(&mut len);
+    let mut buffer = vec![0u8; len as usize];
+    get_log(len, buffer.as_mut_ptr() as *mut GLchar);
+    String::from_utf8_lossy(&buffer).trim_end_matches('\0').to_string()
+}
+
+fn stage_name(ty: GLenum) -> &'static str {
+    match ty {
+        gl::VERTEX_SHADER => "vertex",
+        gl::FRAGMENT_SHADER => "fragment",
+        _ => "unknown",
+    }
+}
+
+// Owns a single GLuint shader object and deletes it on drop, so a failed
+// recompile in `--watch` mode can't leak shader objects.
+struct Shader {
+    id: GLuint,
+}
+
+impl Shader {
+    fn compile(src: &str, ty: GLenum) -> Result<Shader, ShaderError> {
+        let id = gl_safe!(gl::CreateShader(ty), "create shader: initialize a new shader object. Ensure the shader type is correct.");
+        let c_str = CString::new(src.as_bytes()).unwrap();
+        gl_safe!(gl::ShaderSource(id, 1, &c_str.as_ptr(), ptr::null()), "set shader source: provide source code to shader. Ensure the source is valid GLSL.");
+        gl_safe!(gl::CompileShader(id), "compile shader: compile the shader source code.");
+
+        let mut success = gl::FALSE as GLint;
+        unsafe { gl::GetShaderiv(id, gl::COMPILE_STATUS, &mut success); }
+        if success != gl::TRUE as GLint {
+            let log = info_log(
+                |len| unsafe { gl::GetShaderiv(id, gl::INFO_LOG_LENGTH, len) },
+                |len, buf| unsafe { gl::GetShaderInfoLog(id, len, ptr::null_mut(), buf) },
+            );
+            unsafe { gl::DeleteShader(id); }
+            return Err(ShaderError::Compile { stage: stage_name(ty), log });
+        }
+
+        Ok(Shader { id })
+    }
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteShader(self.id); }
+    }
+}
+
+// Owns a linked GLuint program and deletes it on drop. Built from a vertex
+// and fragment `Shader`; the shaders themselves are dropped (and deleted)
+// once linking finishes since the program retains its own reference to them.
+struct ShaderProgram {
+    id: GLuint,
+}
+
+impl ShaderProgram {
+    fn build(vertex_src: &str, fragment_src: &str) -> Result<ShaderProgram, ShaderError> {
+        let vertex = Shader::compile(vertex_src, gl::VERTEX_SHADER)?;
+        let fragment = Shader::compile(fragment_src, gl::FRAGMENT_SHADER)?;
+
+        let id = gl_safe!(gl::CreateProgram(), "create program");
+        gl_safe!(gl::AttachShader(id, vertex.id), "attach vertex shader: link vertex shader to program");
+        gl_safe!(gl::AttachShader(id, fragment.id), "attach fragment shader: link fragment shader to program");
+        // glsl3's vertex source pins `aPos` to location 0 via `layout(location = 0)`,
+        // but GLSL ES 1.00 has no `layout` qualifiers, so without this the driver is
+        // free to assign `aPos` to any attribute index; `setup_fullscreen_quad`
+        // always feeds attribute 0, so bind it explicitly for every profile.
+        let a_pos_name = CString::new("aPos").unwrap();
+        gl_safe!(gl::BindAttribLocation(id, 0, a_pos_name.as_ptr()), "bind attribute location: pin aPos to attribute 0 before linking");
+        gl_safe!(gl::LinkProgram(id), "link program: link all attached shaders");
+
+        let mut success = gl::FALSE as GLint;
+        unsafe { gl::GetProgramiv(id, gl::LINK_STATUS, &mut success); }
+        if success != gl::TRUE as GLint {
+            let log = info_log(
+                |len| unsafe { gl::GetProgramiv(id, gl::INFO_LOG_LENGTH, len) },
+                |len, buf| unsafe { gl::GetProgramInfoLog(id, len, ptr::null_mut(), buf) },
+            );
+            unsafe { gl::DeleteProgram(id); }
+            return Err(ShaderError::Link { log });
+        }
+
+        Ok(ShaderProgram { id })
+    }
+
+    fn use_program(&self) {
+        gl_safe!(gl::UseProgram(self.id), "use shader program");
+    }
+
+    fn uniform_location(&self, name: &str) -> GLint {
+        let c_name = CString::new(name).unwrap();
+        gl_safe!(gl::GetUniformLocation(self.id, c_name.as_ptr()), "get uniform location")
+    }
+
+    // Index assigned to a declared `out vec4 <name>;` fragment output, used
+    // to pick which COLOR_ATTACHMENTn a render target maps to for MRT.
+    fn frag_data_location(&self, name: &str) -> GLint {
+        let c_name = CString::new(name).unwrap();
+        gl_safe!(gl::GetFragDataLocation(self.id, c_name.as_ptr()), "get frag data location")
+    }
+
+    // Enumerates every uniform the linker actually kept (unused ones are
+    // stripped), so callers can bind built-ins and user uniforms only when
+    // they exist instead of assuming a fixed set.
+    fn active_uniforms(&self) -> HashMap<String, UniformInfo> {
+        let mut count = 0;
+        unsafe { gl::GetProgramiv(self.id, gl::ACTIVE_UNIFORMS, &mut count); }
+        let mut max_name_len = 0;
+        unsafe { gl::GetProgramiv(self.id, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_name_len); }
+
+        let mut uniforms = HashMap::new();
+        for index in 0..count {
+            let mut name_buf = vec![0u8; max_name_len.max(1) as usize];
+            let mut written = 0;
+            let mut size = 0;
+            let mut gl_type = 0;
+            unsafe {
+                gl::GetActiveUniform(
+                    self.id,
+                    index as GLuint,
+                    name_buf.len() as GLsizei,
+                    &mut written,
+                    &mut size,
+                    &mut gl_type,
+                    name_buf.as_mut_ptr() as *mut GLchar,
+                );
+            }
+            name_buf.truncate(written as usize);
+            let name = String::from_utf8_lossy(&name_buf).into_owned();
+            // Array uniforms are reported as "name[0]"; strip that so lookups can use the plain name.
+            let name = name.strip_suffix("[0]").map(str::to_string).unwrap_or(name);
+            let location = self.uniform_location(&name);
+            uniforms.insert(name, UniformInfo { location, gl_type });
+        }
+        uniforms
+    }
+}
+
+// Location and GL type of one active uniform, as reported by the linker.
+struct UniformInfo {
+    location: GLint,
+    gl_type: GLenum,
+}
+
+// A typed value for a uniform supplied manually via `--uniform`/`--uniforms-file`,
+// matched against the shader's introspected type before it's bound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UniformData {
+    Float(f32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+    Int(i32),
+    Mat2([f32; 4]),
+    Mat3([f32; 9]),
+    Mat4([f32; 16]),
+}
+
+impl UniformData {
+    fn from_gl_type(gl_type: GLenum, values: &[f32]) -> Result<UniformData, String> {
+        let expect = |n: usize| -> Result<(), String> {
+            if values.len() != n {
+                Err(format!("expected {} component(s), got {}", n, values.len()))
+            } else {
+                Ok(())
+            }
+        };
+        match gl_type {
+            gl::FLOAT => { expect(1)?; Ok(UniformData::Float(values[0])) }
+            gl::FLOAT_VEC2 => { expect(2)?; Ok(UniformData::Vec2(values.try_into().unwrap())) }
+            gl::FLOAT_VEC3 => { expect(3)?; Ok(UniformData::Vec3(values.try_into().unwrap())) }
+            gl::FLOAT_VEC4 => { expect(4)?; Ok(UniformData::Vec4(values.try_into().unwrap())) }
+            gl::INT | gl::BOOL => { expect(1)?; Ok(UniformData::Int(values[0] as i32)) }
+            gl::FLOAT_MAT2 => { expect(4)?; Ok(UniformData::Mat2(values.try_into().unwrap())) }
+            gl::FLOAT_MAT3 => { expect(9)?; Ok(UniformData::Mat3(values.try_into().unwrap())) }
+            gl::FLOAT_MAT4 => { expect(16)?; Ok(UniformData::Mat4(values.try_into().unwrap())) }
+            other => Err(format!("uniform type 0x{:x} isn't supported for manual binding", other)),
+        }
+    }
+
+    fn apply(self, location: GLint) {
+        match self {
+            UniformData::Float(v) => gl_safe!(gl::Uniform1f(location, v), "set float uniform"),
+            UniformData::Vec2(v) => gl_safe!(gl::Uniform2f(location, v[0], v[1]), "set vec2 uniform"),
+            UniformData::Vec3(v) => gl_safe!(gl::Uniform3f(location, v[0], v[1], v[2]), "set vec3 uniform"),
+            UniformData::Vec4(v) => gl_safe!(gl::Uniform4f(location, v[0], v[1], v[2], v[3]), "set vec4 uniform"),
+            UniformData::Int(v) => gl_safe!(gl::Uniform1i(location, v), "set int uniform"),
+            UniformData::Mat2(v) => gl_safe!(gl::UniformMatrix2fv(location, 1, gl::FALSE, v.as_ptr()), "set mat2 uniform"),
+            UniformData::Mat3(v) => gl_safe!(gl::UniformMatrix3fv(location, 1, gl::FALSE, v.as_ptr()), "set mat3 uniform"),
+            UniformData::Mat4(v) => gl_safe!(gl::UniformMatrix4fv(location, 1, gl::FALSE, v.as_ptr()), "set mat4 uniform"),
+        }
+    }
+}
+
+// Parses a repeatable `--uniform name=v1,v2,...` argument into its raw components.
+fn parse_uniform_arg(arg: &str) -> (String, Vec<f32>) {
+    let (name, values) = arg.split_once('=')
+        .unwrap_or_else(|| panic!("Invalid --uniform '{}': expected name=v1,v2,...", arg));
+    let values = values.split(',')
+        .map(|v| v.trim().parse().unwrap_or_else(|_| panic!("Invalid numeric value in --uniform '{}'", arg)))
+        .collect();
+    (name.to_string(), values)
+}
+
+// Loads a `--uniforms-file` JSON manifest mapping uniform names to a number or array of numbers.
+fn load_uniform_manifest(path: &str) -> Vec<(String, Vec<f32>)> {
+    let contents = std::fs::read_to_string(path).expect("Failed to read uniform manifest file");
+    let json: serde_json::Value = serde_json::from_str(&contents).expect("Failed to parse uniform manifest as JSON");
+    let object = json.as_object().expect("Uniform manifest must be a JSON object mapping names to value(s)");
+
+    object.iter().map(|(name, value)| {
+        let values = match value {
+            serde_json::Value::Number(n) => vec![n.as_f64().unwrap() as f32],
+            serde_json::Value::Array(items) => items.iter()
+                .map(|item| item.as_f64().unwrap_or_else(|| panic!("Uniform '{}' manifest value must be numeric", name)) as f32)
+                .collect(),
+            _ => panic!("Uniform '{}' manifest value must be a number or array of numbers", name),
+        };
+        (name.clone(), values)
+    }).collect()
+}
+
+// Looks up `name` among the introspected uniforms, type-checks the raw
+// values against it, and binds it if everything matches.
+fn bind_manual_uniform(uniforms: &HashMap<String, UniformInfo>, name: &str, values: &[f32]) {
+    let info = uniforms.get(name)
+        .unwrap_or_else(|| panic!("Uniform '{}' is not declared (or not active) in the shader.", name));
+    let data = UniformData::from_gl_type(info.gl_type, values)
+        .unwrap_or_else(|err| panic!("Uniform '{}' {}.", name, err));
+    data.apply(info.location);
+}
+
+impl Drop for ShaderProgram {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteProgram(self.id); }
+    }
+}
+
+// One fragment-shader `out vec4` routed to its own texture and color
+// attachment, enabling multiple render targets from a single draw call.
+struct OutputTarget {
+    name: String,
+    attachment: GLenum,
+}
+
+// An image file bound as a `sampler2D` input, uploaded once at startup and
+// kept on its own texture unit (`iChannelN`, where N is the channel index
+// parsed out of the `--texture` target name) for the lifetime of the run.
+struct LoadedTexture {
+    channel: usize,
+    texture: GLuint,
+    width: u32,
+    height: u32,
+}
+
+// Parses a repeatable `--texture iChannelN=path` argument.
+fn parse_texture_arg(arg: &str) -> (String, String) {
+    let (name, path) = arg.split_once('=')
+        .unwrap_or_else(|| panic!("Invalid --texture '{}': expected iChannelN=path", arg));
+    (name.to_string(), path.to_string())
+}
+
+// Extracts N out of an "iChannelN" target name.
+fn parse_channel_index(name: &str) -> usize {
+    name.strip_prefix("iChannel")
+        .and_then(|n| n.parse().ok())
+        .unwrap_or_else(|| panic!("Invalid --texture target '{}': expected iChannelN", name))
+}
+
+// Decodes an image file and uploads it as a GL_RGB/GL_RGBA texture,
+// picking the format by whether the source has an alpha channel.
+fn load_texture(path: &str) -> (GLuint, u32, u32) {
+    let img = image::open(path).unwrap_or_else(|err| panic!("Failed to load texture '{}': {}", path, err));
+    let (width, height) = (img.width(), img.height());
+
+    let mut texture = 0;
+    gl_safe!(gl::GenTextures(1, &mut texture), "generate texture: create a new texture object");
+    gl_safe!(gl::BindTexture(gl::TEXTURE_2D, texture), "bind texture: set the texture as active");
+
+    if img.color().has_alpha() {
+        let rgba = img.to_rgba8();
+        gl_safe!(gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as i32, width as i32, height as i32, 0, gl::RGBA, gl::UNSIGNED_BYTE, rgba.as_raw().as_ptr() as *const _), "upload texture image: provide decoded pixel data");
+    } else {
+        let rgb = img.to_rgb8();
+        gl_safe!(gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as i32, width as i32, height as i32, 0, gl::RGB, gl::UNSIGNED_BYTE, rgb.as_raw().as_ptr() as *const _), "upload texture image: provide decoded pixel data");
+    }
+
+    gl_safe!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32), "set texture wrap S: define horizontal wrap mode");
+    gl_safe!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32), "set texture wrap T: define vertical wrap mode");
+    gl_safe!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32), "set texture min filter: define texture minification filter");
+    gl_safe!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32), "set texture mag filter: define texture magnification filter");
+
+    (texture, width, height)
+}
+
+// Binds every loaded texture to its channel's texture unit and sets the
+// matching `iChannelN`/`iChannelResolution[N]` uniforms on `program`. Run
+// once at startup and again after every `--watch` reload, since uniform
+// locations are only valid for the program they were queried from.
+fn apply_texture_uniforms(program: &ShaderProgram, textures: &[LoadedTexture]) {
+    for texture in textures {
+        gl_safe!(gl::ActiveTexture(gl::TEXTURE0 + texture.channel as GLenum), "select texture unit");
+        gl_safe!(gl::BindTexture(gl::TEXTURE_2D, texture.texture), "bind input texture");
+
+        let sampler_loc = program.uniform_location(&format!("iChannel{}", texture.channel));
+        if sampler_loc != -1 {
+            gl_safe!(gl::Uniform1i(sampler_loc, texture.channel as GLint), "bind sampler uniform to texture unit");
+        }
+
+        let resolution_loc = program.uniform_location(&format!("iChannelResolution[{}]", texture.channel));
+        if resolution_loc != -1 {
+            gl_safe!(gl::Uniform3f(resolution_loc, texture.width as f32, texture.height as f32, 1.0), "set iChannelResolution uniform: set uniform value");
+        }
+    }
+}
+
+// Reads the fragment shader off disk, applies the profile header, and links
+// it against the profile's vertex source. Used both for the initial build
+// and for every `--watch` reload.
+fn build_program(shader_path: &str, profile: ShaderVersion) -> Result<ShaderProgram, ShaderError> {
+    let fs_src = std::fs::read_to_string(shader_path).expect("Failed to read shader file");
+    let fs_src = prepare_fragment_source(&fs_src, profile);
+    ShaderProgram::build(profile.vertex_source(), &fs_src)
+}
+
+// Builds the VAO/VBO for the full-screen triangle-fan quad every render
+// path draws into its framebuffer(s) with.
+fn setup_fullscreen_quad() -> GLuint {
+    let vertices: [f32; 12] = [
+        -1.0, -1.0, 0.0,
+         1.0, -1.0, 0.0,
+         1.0,  1.0, 0.0,
+        -1.0,  1.0, 0.0,
+    ];
+
+    let mut vbo = 0;
+    let mut vao = 0;
+    gl_safe!(gl::GenVertexArrays(1, &mut vao), "generating VAO");
+    gl_safe!(gl::GenBuffers(1, &mut vbo), "generating VBO");
+
+    gl_safe!(gl::BindVertexArray(vao), "binding VAO");
+    gl_safe!(gl::BindBuffer(gl::ARRAY_BUFFER, vbo), "binding VBO");
+    gl_safe!(gl::BufferData(gl::ARRAY_BUFFER, (vertices.len() * std::mem::size_of::<f32>()) as GLsizeiptr, vertices.as_ptr() as *const _, gl::STATIC_DRAW), "buffering vertex data");
+    gl_safe!(gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as GLsizei, ptr::null()), "setting vertex attrib pointer");
+    gl_safe!(gl::EnableVertexAttribArray(0), "enabling vertex attrib array");
+    vao
+}
+
+// One stage of a `--passes` manifest, as parsed from JSON.
+struct PassConfig {
+    name: String,
+    shader: String,
+    inputs: Vec<String>,
+}
+
+// Reads a `--passes` manifest: an ordered list of passes, each naming the
+// other passes (by name, including itself for feedback) whose output
+// textures it samples as iChannel0..N, plus which pass's output is the
+// one that gets read back and written to stdout.
+fn load_pass_manifest(path: &str) -> (Vec<PassConfig>, String) {
+    let contents = std::fs::read_to_string(path).expect("Failed to read passes manifest file");
+    let json: serde_json::Value = serde_json::from_str(&contents).expect("Failed to parse passes manifest as JSON");
+    let passes_json = json.get("passes").and_then(|v| v.as_array())
+        .expect("Passes manifest must have a \"passes\" array");
+
+    let passes: Vec<PassConfig> = passes_json.iter().map(|entry| {
+        let name = entry.get("name").and_then(|v| v.as_str())
+            .expect("Each pass needs a \"name\"").to_string();
+        let shader = entry.get("shader").and_then(|v| v.as_str())
+            .expect("Each pass needs a \"shader\" path").to_string();
+        let inputs = entry.get("inputs").and_then(|v| v.as_array())
+            .map(|arr| arr.iter()
+                .map(|v| v.as_str().expect("Pass input names must be strings").to_string())
+                .collect())
+            .unwrap_or_default();
+        PassConfig { name, shader, inputs }
+    }).collect();
+
+    let final_pass = json.get("final").and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| passes.last().expect("Passes manifest must declare at least one pass").name.clone());
+
+    (passes, final_pass)
+}
+
+// A running pass: its program, the sampler uniform for each declared
+// input, and a pair of framebuffer/texture targets it ping-pongs between
+// so a feedback pass can read its own previous frame while writing the
+// next one.
+struct Pass {
+    name: String,
+    program: ShaderProgram,
+    inputs: Vec<String>,
+    channel_locs: Vec<GLint>,
+    i_resolution_loc: GLint,
+    i_time_loc: GLint,
+    framebuffers: [GLuint; 2],
+    textures: [GLuint; 2],
+    current: usize,
+}
+
+// Allocates an RGBA framebuffer/texture pair later passes can sample from.
+fn create_render_target(width: u32, height: u32) -> (GLuint, GLuint) {
+    let mut framebuffer = 0;
+    gl_safe!(gl::GenFramebuffers(1, &mut framebuffer), "generate framebuffer: create a new framebuffer object");
+    gl_safe!(gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer), "bind framebuffer: set the framebuffer as active");
+
+    let mut texture = 0;
+    gl_safe!(gl::GenTextures(1, &mut texture), "generate texture: create a new texture object");
+    gl_safe!(gl::BindTexture(gl::TEXTURE_2D, texture), "bind texture: set the texture as active");
+    gl_safe!(gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as i32, width as i32, height as i32, 0, gl::RGBA, gl::UNSIGNED_BYTE, ptr::null()), "create texture image: allocate storage for texture");
+    gl_safe!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32), "set texture min filter: define texture minification filter");
+    gl_safe!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32), "set texture mag filter: define texture magnification filter");
+    gl_safe!(gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, texture, 0), "attach texture to framebuffer: link texture to framebuffer");
+
+    if gl_safe!(gl::CheckFramebufferStatus(gl::FRAMEBUFFER), "check framebuffer status: verify framebuffer completeness") != gl::FRAMEBUFFER_COMPLETE {
+        panic!("Framebuffer is not complete. Check framebuffer attachments and ensure they are correctly configured.");
+    }
+
+    (framebuffer, texture)
+}
+
+// Builds and runs a multi-pass render graph: each pass renders full-screen
+// into its own framebuffer and exposes its output to later passes (or
+// itself, for feedback) as iChannel0..N. Only the manifest's "final" pass
+// is read back and streamed to stdout.
+fn run_multi_pass(passes_path: &str, profile: ShaderVersion, width: u32, height: u32, fps: u32, duration: u32) {
+    let (configs, final_pass) = load_pass_manifest(passes_path);
+
+    let pass_index: HashMap<String, usize> = configs.iter().enumerate()
+        .map(|(i, c)| (c.name.clone(), i))
+        .collect();
+
+    let mut passes: Vec<Pass> = configs.into_iter().map(|config| {
+        let program = build_program(&config.shader, profile)
+            .unwrap_or_else(|err| panic!("Pass '{}': {}", config.name, err));
+        let i_resolution_loc = program.uniform_location("iResolution");
+        let i_time_loc = program.uniform_location("iTime");
+        let channel_locs = (0..config.inputs.len())
+            .map(|i| program.uniform_location(&format!("iChannel{}", i)))
+            .collect();
+        let (fb_a, tex_a) = create_render_target(width, height);
+        let (fb_b, tex_b) = create_render_target(width, height);
+
+        Pass {
+            name: config.name,
+            program,
+            inputs: config.inputs,
+            channel_locs,
+            i_resolution_loc,
+            i_time_loc,
+            framebuffers: [fb_a, fb_b],
+            textures: [tex_a, tex_b],
+            current: 0,
+        }
+    }).collect();
+
+    if !pass_index.contains_key(&final_pass) {
+        panic!("Passes manifest's \"final\" pass '{}' doesn't match any declared pass", final_pass);
+    }
+
+    let mut pixels = vec![0u8; (width * height * 3) as usize];
+
+    for frame in 0..(fps * duration) {
+        for i in 0..passes.len() {
+            let input_textures: Vec<GLuint> = passes[i].inputs.iter().map(|input_name| {
+                let input_index = *pass_index.get(input_name)
+                    .unwrap_or_else(|| panic!("Pass '{}' references unknown input '{}'", passes[i].name, input_name));
+                let input = &passes[input_index];
+                input.textures[input.current]
+            }).collect();
+
+            let target = 1 - passes[i].current;
+            let pass = &mut passes[i];
+
+            gl_safe!(gl::BindFramebuffer(gl::FRAMEBUFFER, pass.framebuffers[target]), "bind framebuffer: set the framebuffer as active");
+            pass.program.use_program();
+
+            if pass.i_resolution_loc != -1 {
+                gl_safe!(gl::Uniform3f(pass.i_resolution_loc, width as f32, height as f32, 0.0), "set iResolution uniform: set uniform value");
+            }
+            if pass.i_time_loc != -1 {
+                gl_safe!(gl::Uniform1f(pass.i_time_loc, frame as f32 / fps as f32), "setting uniform value for iTime");
+            }
+
+            for (unit, (&texture, &loc)) in input_textures.iter().zip(pass.channel_locs.iter()).enumerate() {
+                gl_safe!(gl::ActiveTexture(gl::TEXTURE0 + unit as GLenum), "select texture unit");
+                gl_safe!(gl::BindTexture(gl::TEXTURE_2D, texture), "bind input texture");
+                if loc != -1 {
+                    gl_safe!(gl::Uniform1i(loc, unit as GLint), "bind sampler uniform to texture unit");
+                }
+            }
+
+            gl_safe!(gl::Clear(gl::COLOR_BUFFER_BIT), "clearing framebuffer");
+            gl_safe!(gl::DrawArrays(gl::TRIANGLE_FAN, 0, 4), "drawing arrays");
+
+            pass.current = target;
+
+            if pass.name == final_pass {
+                gl_safe!(gl::ReadPixels(0, 0, width as i32, height as i32, gl::RGB, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _), "reading pixels");
+                io::stdout().write_all(&pixels).unwrap();
+            }
+        }
+    }
 }
 
 fn main() {
@@ -64,7 +647,7 @@ fn main() {
         .author("Anton Bukov <k06aaa@gmail.com>")
         .about("Renders a shader to a video file")
         .arg(Arg::new("shader")
-            .help("Path to the shader file")
+            .help("Path to the shader file (ignored when --passes is given)")
             .required(true)
             .index(1))
         .arg(Arg::new("width")
@@ -83,13 +666,66 @@ fn main() {
             .help("Duration of the video in seconds")
             .required(true)
             .index(5))
+        .arg(Arg::new("profile")
+            .long("profile")
+            .help("GLSL version/profile target for the shader")
+            .possible_values(&["glsl3", "gles2"])
+            .default_value("glsl3"))
+        .arg(Arg::new("watch")
+            .long("watch")
+            .help("Recompile the shader live whenever the file on disk changes")
+            .takes_value(false))
+        .arg(Arg::new("outputs")
+            .long("outputs")
+            .help("Comma-separated fragment output names to render as separate targets (MRT); a single output streams to stdout, multiple ones each stream to their own <name>.raw file")
+            .default_value("FragColor"))
+        .arg(Arg::new("uniform")
+            .long("uniform")
+            .help("Bind an arbitrary uniform as name=v1,v2,... (repeatable); type is matched against the shader's introspected uniforms")
+            .takes_value(true)
+            .multiple_occurrences(true))
+        .arg(Arg::new("uniforms-file")
+            .long("uniforms-file")
+            .help("JSON manifest mapping uniform names to a number or array of numbers")
+            .takes_value(true))
+        .arg(Arg::new("passes")
+            .long("passes")
+            .help("JSON manifest describing a multi-pass render graph; when given, the positional shader and the outputs/uniform flags are ignored and --texture is rejected")
+            .takes_value(true))
+        .arg(Arg::new("texture")
+            .long("texture")
+            .help("Bind an image file as a sampler channel: iChannelN=path.png (repeatable); not supported together with --passes")
+            .takes_value(true)
+            .multiple_occurrences(true))
         .get_matches();
 
-    let shader_path = matches.value_of("shader").unwrap();
+    let shader_path = matches.value_of("shader").unwrap_or("");
+    if shader_path.is_empty() && !matches.is_present("passes") {
+        panic!("The <shader> argument is required unless --passes is given");
+    }
     let width: u32 = matches.value_of("width").unwrap().parse().expect("Invalid width");
     let height: u32 = matches.value_of("height").unwrap().parse().expect("Invalid height");
     let fps: u32 = matches.value_of("fps").unwrap().parse().expect("Invalid fps");
     let duration: u32 = matches.value_of("duration").unwrap().parse().expect("Invalid duration");
+    let profile: ShaderVersion = matches.value_of("profile").unwrap().parse().expect("Invalid profile");
+    let watch = matches.is_present("watch");
+    let output_names: Vec<String> = matches.value_of("outputs").unwrap()
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .collect();
+
+    let mut manual_uniforms: Vec<(String, Vec<f32>)> = matches.values_of("uniform")
+        .map(|vs| vs.map(parse_uniform_arg).collect())
+        .unwrap_or_default();
+    if let Some(path) = matches.value_of("uniforms-file") {
+        manual_uniforms.extend(load_uniform_manifest(path));
+    }
+    let texture_args: Vec<String> = matches.values_of("texture")
+        .map(|vs| vs.map(String::from).collect())
+        .unwrap_or_default();
+    if matches.is_present("passes") && !texture_args.is_empty() {
+        panic!("--texture isn't supported together with --passes.");
+    }
 
     // Create an invisible OpenGL context
     let el = glutin::event_loop::EventLoop::new();
@@ -103,116 +739,195 @@ fn main() {
     // Load OpenGL functions
     gl_safe!(gl::load_with(|symbol| windowed_context.get_proc_address(symbol) as *const _), "loading OpenGL functions");
 
-    // Load and compile vertex shader source from constant
-    let vertex_shader_src = "#version 330 core\nlayout (location = 0) in vec3 aPos;\nvoid main() {\ngl_Position = vec4(aPos, 1.0);\n}";
-    let vs = compile_shader(vertex_shader_src, gl::VERTEX_SHADER);
+    // Create and bind the full-screen quad shared by every render path
+    let vao = setup_fullscreen_quad();
+    gl_safe!(gl::Viewport(0, 0, width as i32, height as i32), "setting viewport");
+    gl_safe!(gl::ClearColor(0.0, 0.0, 0.0, 1.0), "setting clear color");
+    gl_safe!(gl::BindVertexArray(vao), "binding vertex array");
 
-    // Load and compile fragment shader source from file
-    let fs_src = std::fs::read_to_string(shader_path).expect("Failed to read shader file");
-    let fs = compile_shader(&fs_src, gl::FRAGMENT_SHADER);
-
-    // Create a program and attach the fragment shader
-    let program = gl_safe!(gl::CreateProgram(), "create program");
-    gl_safe!(gl::AttachShader(program, vs), "attach vertex shader: link vertex shader to program");
-    gl_safe!(gl::AttachShader(program, fs), "attach fragment shader: link fragment shader to program");
-    gl_safe!(gl::LinkProgram(program), "link program: link all attached shaders");
-    gl_safe!(gl::UseProgram(program), "use program: activate the shader program");
-
-    // Check for linking errors
-    let mut success = gl::FALSE as GLint;
-    gl_safe!(gl::GetProgramiv(program, gl::LINK_STATUS, &mut success), "check link status: verify program linking success");
-    if success != gl::TRUE as GLint {
-        let mut len = 0;
-        gl_safe!(gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len), "get program info log length: determine length of linking log");
-        let mut buffer = vec![0u8; len as usize];
-        gl_safe!(gl::GetProgramInfoLog(program, len, ptr::null_mut(), buffer.as_mut_ptr() as *mut GLchar), "get program info log: retrieve linking log");
-        
-        panic!("Program linking failed: {}. Verify that all shaders are correctly attached and compiled.", str::from_utf8(&buffer).unwrap());
+    if let Some(passes_path) = matches.value_of("passes") {
+        run_multi_pass(passes_path, profile, width, height, fps, duration);
+        return;
     }
 
-    // Use the shader program
-    gl_safe!(gl::UseProgram(program), "use shader program");
+    // Build the initial shader program; a bad shader here is fatal since
+    // there's no previous program to fall back to yet.
+    let mut program = build_program(shader_path, profile).unwrap_or_else(|err| panic!("{}", err));
+    program.use_program();
+
+    // Decode and upload every `--texture` input once; they stay resident on
+    // their own texture units for the lifetime of the run.
+    let loaded_textures: Vec<LoadedTexture> = texture_args.iter().map(|arg| {
+        let (name, path) = parse_texture_arg(arg);
+        let channel = parse_channel_index(&name);
+        let (texture, width, height) = load_texture(&path);
+        LoadedTexture { channel, texture, width, height }
+    }).collect();
 
     // Create a framebuffer
     let mut framebuffer = 0;
     gl_safe!(gl::GenFramebuffers(1, &mut framebuffer), "generate framebuffer: create a new framebuffer object");
     gl_safe!(gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer), "bind framebuffer: set the framebuffer as active");
 
-    // Create a texture to render to
-    let mut texture = 0;
-    gl_safe!(gl::GenTextures(1, &mut texture), "generate texture: create a new texture object");
-    gl_safe!(gl::BindTexture(gl::TEXTURE_2D, texture), "bind texture: set the texture as active");
-    gl_safe!(gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as i32, width as i32, height as i32, 0, gl::RGB, gl::UNSIGNED_BYTE, ptr::null()), "create texture image: allocate storage for texture");
-    gl_safe!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32), "set texture min filter: define texture minification filter");
-    gl_safe!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32), "set texture mag filter: define texture magnification filter");
-    gl_safe!(gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, texture, 0), "attach texture to framebuffer: link texture to framebuffer");
+    // Resolve each requested fragment output to the attachment index the
+    // linker assigned it, and give each its own render-target texture. The
+    // framebuffer's attachments and `DrawBuffers` call are sized for this
+    // mapping; a `--watch` reload that changes which attachment the linker
+    // picks for a given output would silently write/read the wrong
+    // attachment, so reloads re-check (but don't re-attach) this mapping
+    // below.
+    // Under gles2, `FragColor` is the `gl_FragColor` builtin (see
+    // `ShaderVersion::compat_preamble`), which always writes draw buffer 0
+    // and has no location for `glGetFragDataLocation` to report.
+    let output_location = |program: &ShaderProgram, name: &str| -> GLint {
+        if profile == ShaderVersion::Gles2 && name == "FragColor" {
+            0
+        } else {
+            program.frag_data_location(name)
+        }
+    };
+    let outputs: Vec<OutputTarget> = output_names.iter().map(|name| {
+        let location = output_location(&program, name);
+        if location == -1 {
+            panic!("Fragment output '{}' not found. Ensure it is declared as `out vec4 {}` in the shader.", name, name);
+        }
+        let attachment = gl::COLOR_ATTACHMENT0 + location as GLenum;
+
+        let mut texture = 0;
+        gl_safe!(gl::GenTextures(1, &mut texture), "generate texture: create a new texture object");
+        gl_safe!(gl::BindTexture(gl::TEXTURE_2D, texture), "bind texture: set the texture as active");
+        gl_safe!(gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as i32, width as i32, height as i32, 0, gl::RGB, gl::UNSIGNED_BYTE, ptr::null()), "create texture image: allocate storage for texture");
+        gl_safe!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32), "set texture min filter: define texture minification filter");
+        gl_safe!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32), "set texture mag filter: define texture magnification filter");
+        gl_safe!(gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, texture, 0), "attach texture to framebuffer: link texture to framebuffer");
+
+        OutputTarget { name: name.clone(), attachment }
+    }).collect();
+
+    // Tell the framebuffer which attachments the fragment shader actually
+    // writes to; gaps in the attachment list are filled with GL_NONE.
+    let max_attachment = outputs.iter().map(|o| o.attachment).max().unwrap_or(gl::COLOR_ATTACHMENT0);
+    let mut draw_buffers = vec![gl::NONE; (max_attachment - gl::COLOR_ATTACHMENT0 + 1) as usize];
+    for output in &outputs {
+        draw_buffers[(output.attachment - gl::COLOR_ATTACHMENT0) as usize] = output.attachment;
+    }
+    gl_safe!(gl::DrawBuffers(draw_buffers.len() as GLsizei, draw_buffers.as_ptr()), "set draw buffers: declare which attachments the shader writes to");
 
     // Check if framebuffer is complete
     if gl_safe!(gl::CheckFramebufferStatus(gl::FRAMEBUFFER), "check framebuffer status: verify framebuffer completeness") != gl::FRAMEBUFFER_COMPLETE {
         panic!("Framebuffer is not complete. Check framebuffer attachments and ensure they are correctly configured.");
     }
 
-    // Set iResolution uniform
-    let i_resolution_cstr = CString::new("iResolution").unwrap();
-    let i_resolution_loc = gl_safe!(gl::GetUniformLocation(program, i_resolution_cstr.as_ptr()), "get iResolution location: find uniform location");
-    if i_resolution_loc == -1 {
-        panic!("Failed to get uniform location for iResolution. Ensure the uniform variable is declared in the shader.");
-    }
-    gl_safe!(gl::Uniform3f(i_resolution_loc, width as f32, height as f32, 0.0), "set iResolution uniform: set uniform value");
+    // A single output streams raw frames to stdout (matching the original,
+    // ffmpeg-pipeable behavior); multiple outputs each get their own file
+    // so a pass can emit color plus auxiliary channels side by side.
+    let mut writers: Vec<Box<dyn Write>> = if outputs.len() == 1 {
+        vec![Box::new(io::stdout())]
+    } else {
+        outputs.iter().map(|output| {
+            let path = format!("{}.raw", output.name);
+            Box::new(std::fs::File::create(&path).expect("Failed to create output file")) as Box<dyn Write>
+        }).collect()
+    };
+
+    // Introspect the linked program's active uniforms so iResolution/iTime
+    // (and any --uniform bindings) are only set when the shader actually
+    // declares and uses them, instead of requiring a fixed uniform set.
+    let mut uniforms = program.active_uniforms();
 
-    // Get iTime uniform location to use inside of the render loop
-    let i_time_cstr = CString::new("iTime").unwrap();
-    let i_time_loc = gl_safe!(gl::GetUniformLocation(program, i_time_cstr.as_ptr()), "getting uniform location for iTime");
-    if i_time_loc == -1 {
-        panic!("Failed to get uniform location for iTime. Ensure the uniform variable is declared in the shader.");
+    let mut i_resolution_loc = uniforms.get("iResolution").map(|u| u.location).unwrap_or(-1);
+    if i_resolution_loc != -1 {
+        gl_safe!(gl::Uniform3f(i_resolution_loc, width as f32, height as f32, 0.0), "set iResolution uniform: set uniform value");
     }
 
-    // Create and configure a vertex buffer for the rectangle
-    let vertices: [f32; 12] = [
-        -1.0, -1.0, 0.0,
-         1.0, -1.0, 0.0,
-         1.0,  1.0, 0.0,
-        -1.0,  1.0, 0.0,
-    ];
+    let mut i_time_loc = uniforms.get("iTime").map(|u| u.location).unwrap_or(-1);
 
-    let mut vbo = 0;
-    let mut vao = 0;
-    gl_safe!(gl::GenVertexArrays(1, &mut vao), "generating VAO");
-    gl_safe!(gl::GenBuffers(1, &mut vbo), "generating VBO");
+    apply_texture_uniforms(&program, &loaded_textures);
 
-    gl_safe!(gl::BindVertexArray(vao), "binding VAO");
-    gl_safe!(gl::BindBuffer(gl::ARRAY_BUFFER, vbo), "binding VBO");
-    gl_safe!(gl::BufferData(gl::ARRAY_BUFFER, (vertices.len() * std::mem::size_of::<f32>()) as GLsizeiptr, vertices.as_ptr() as *const _, gl::STATIC_DRAW), "buffering vertex data");
-    gl_safe!(gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as GLsizei, ptr::null()), "setting vertex attrib pointer");
-    gl_safe!(gl::EnableVertexAttribArray(0), "enabling vertex attrib array");
+    for (name, values) in &manual_uniforms {
+        bind_manual_uniform(&uniforms, name, values);
+    }
+
+    // Track the shader file's mtime so `--watch` only reloads on real edits.
+    let mut shader_mtime = std::fs::metadata(shader_path).and_then(|m| m.modified()).ok();
 
     // Create a vector for pixels once before the loop
     let mut pixels = vec![0u8; (width * height * 3) as usize];
 
-    // Render to the framebuffer
-    gl_safe!(gl::Viewport(0, 0, width as i32, height as i32), "setting viewport");
-    gl_safe!(gl::ClearColor(0.0, 0.0, 0.0, 1.0), "setting clear color");
     gl_safe!(gl::Clear(gl::COLOR_BUFFER_BIT), "clearing framebuffer");
 
-    // Render the rectangle
-    gl_safe!(gl::BindVertexArray(vao), "binding vertex array");
-
     // Main rendering loop
     for frame in 0..(fps * duration) {
-        // Set iTime uniform
-        gl_safe!(gl::Uniform1f(i_time_loc, frame as f32 / fps as f32), "setting uniform value for iTime");
+        // In `--watch` mode, poll the shader file's mtime between frames and
+        // recompile on change, keeping the last known-good program if the
+        // edit doesn't compile or link.
+        if watch {
+            if let Ok(mtime) = std::fs::metadata(shader_path).and_then(|m| m.modified()) {
+                if Some(mtime) != shader_mtime {
+                    shader_mtime = Some(mtime);
+                    match build_program(shader_path, profile) {
+                        Ok(new_program) => {
+                            // The framebuffer's attachments and `DrawBuffers` call were
+                            // set up for the *original* program's frag-data assignment;
+                            // if the edit reordered or renamed outputs, the linker may
+                            // hand back a different attachment for the same name and
+                            // every subsequent frame would write/read the wrong
+                            // texture. Rather than reconcile that silently, refuse the
+                            // reload and keep the last known-good program.
+                            let mut mismatch = None;
+                            for output in &outputs {
+                                let location = output_location(&new_program, &output.name);
+                                if location == -1 {
+                                    mismatch = Some(format!("output '{}' is no longer declared in the shader", output.name));
+                                    break;
+                                }
+                                let attachment = gl::COLOR_ATTACHMENT0 + location as GLenum;
+                                if attachment != output.attachment {
+                                    mismatch = Some(format!("output '{}' moved to a different attachment ({} -> {}); restart to pick up the new layout", output.name, output.attachment, attachment));
+                                    break;
+                                }
+                            }
+                            if let Some(reason) = mismatch {
+                                eprintln!("Shader reload failed, keeping previous program: {}", reason);
+                            } else {
+                                new_program.use_program();
+                                uniforms = new_program.active_uniforms();
+                                i_resolution_loc = uniforms.get("iResolution").map(|u| u.location).unwrap_or(-1);
+                                i_time_loc = uniforms.get("iTime").map(|u| u.location).unwrap_or(-1);
+                                apply_texture_uniforms(&new_program, &loaded_textures);
+                                for (name, values) in &manual_uniforms {
+                                    bind_manual_uniform(&uniforms, name, values);
+                                }
+                                program = new_program;
+                            }
+                        }
+                        Err(err) => {
+                            eprintln!("Shader reload failed, keeping previous program: {}", err);
+                        }
+                    }
+                }
+            }
+        }
 
-        // Set iResolution uniform
-        gl_safe!(gl::Uniform3f(i_resolution_loc, width as f32, height as f32, 0.0), "set iResolution uniform: set uniform value");
+        // Set iTime uniform, if the shader declares it
+        if i_time_loc != -1 {
+            gl_safe!(gl::Uniform1f(i_time_loc, frame as f32 / fps as f32), "setting uniform value for iTime");
+        }
+
+        // Set iResolution uniform, if the shader declares it
+        if i_resolution_loc != -1 {
+            gl_safe!(gl::Uniform3f(i_resolution_loc, width as f32, height as f32, 0.0), "set iResolution uniform: set uniform value");
+        }
 
         // Render the rectangle
         gl_safe!(gl::DrawArrays(gl::TRIANGLE_FAN, 0, 4), "drawing arrays");
 
-        // Read pixels from the framebuffer
-        gl_safe!(gl::ReadPixels(0, 0, width as i32, height as i32, gl::RGB, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _), "reading pixels");
-
-        // Write pixels to stdout
-        io::stdout().write_all(&pixels).unwrap();
+        // Read back each attachment in turn and stream it to its own output
+        for (output, writer) in outputs.iter().zip(writers.iter_mut()) {
+            gl_safe!(gl::ReadBuffer(output.attachment), "selecting read buffer: choose which attachment to read back");
+            gl_safe!(gl::ReadPixels(0, 0, width as i32, height as i32, gl::RGB, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _), "reading pixels");
+            writer.write_all(&pixels).unwrap();
+        }
     }
 }
 
@@ -274,9 +989,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_gles2_headerless_shader() {
+        // A shader authored without its own `#version` directive, compiled
+        // under `--profile gles2`, exercises the header/preamble injection
+        // (and the `out vec4 FragColor;` -> `gl_FragColor` rewrite) end to
+        // end instead of short-circuiting on an already-versioned source.
+        let shader_source = r#"
+        out vec4 FragColor;
+        void main() {
+            FragColor = vec4(0.4, 0.6, 0.8, 1.0);
+        }
+        "#;
+
+        let mut shader_path = env::temp_dir();
+        shader_path.push("temp_shader_test_gles2_headerless_shader.frag");
+        let mut file = File::create(&shader_path).expect("Failed to create shader file");
+        file.write_all(shader_source.as_bytes()).expect("Failed to write shader source");
+        let shader_path_str = shader_path.to_str().unwrap().to_string();
+
+        let result = run_playder("test_gles2_headerless_shader", &[
+            &shader_path_str, "1", "1", "1", "1",
+            "--profile", "gles2",
+        ]);
+
+        std::fs::remove_file(&shader_path).expect("Failed to remove temporary shader file");
+
+        match result {
+            Ok(output) => assert_eq!(output, vec![102, 153, 204]),
+            Err(err) => panic!("Test failed with error: {}", err),
+        }
+    }
+
     #[test]
     fn test_missing_itime_uniform() {
-        // Shader source without iTime uniform
+        // Shader source without an iTime uniform should still render: iTime
+        // is only bound when the shader actually declares it.
         let shader_source = r#"
         #version 330 core
         uniform vec3 iResolution;
@@ -286,16 +1034,24 @@ mod tests {
         }
         "#;
 
-        // Run the test
+        let expected_output = vec![
+            255, 255, 0,
+            255, 255, 0,
+            255, 255, 0,
+            255, 255, 0,
+            255, 255, 0,
+        ];
+
         match render_shader("test_missing_itime_uniform", shader_source, 1, 1, 5, 1) {
-            Ok(_) => panic!("Test should have failed due to missing iTime uniform"),
-            Err(err) => assert!(err.contains("Failed to get uniform location for iTime"), "Unexpected error message: {}", err),
+            Ok(output) => assert_eq!(output, expected_output),
+            Err(err) => panic!("Test failed with error: {}", err),
         }
     }
 
     #[test]
     fn test_missing_iresolution_uniform() {
-        // Shader source without iResolution uniform
+        // Shader source without an iResolution uniform should still render:
+        // iResolution is only bound when the shader actually declares it.
         let shader_source = r#"
         #version 330 core
         uniform float iTime;
@@ -305,10 +1061,163 @@ mod tests {
         }
         "#;
 
-        // Run the test
+        let expected_output = vec![
+            0, 0, 0,
+            51, 0, 0,
+            102, 0, 0,
+            153, 0, 0,
+            204, 0, 0,
+        ];
+
         match render_shader("test_missing_iresolution_uniform", shader_source, 1, 1, 5, 1) {
-            Ok(_) => panic!("Test should have failed due to missing iResolution uniform"),
-            Err(err) => assert!(err.contains("Failed to get uniform location for iResolution"), "Unexpected error message: {}", err),
+            Ok(output) => assert_eq!(output, expected_output),
+            Err(err) => panic!("Test failed with error: {}", err),
+        }
+    }
+
+    // Generalized variant of `render_shader` for tests that need extra CLI
+    // flags (`--outputs`, `--uniform`, `--passes`) instead of just a bare
+    // shader positional.
+    fn run_playder(test_name: &str, args: &[&str]) -> Result<Vec<u8>, String> {
+        let output = Command::new("cargo")
+            .env("CARGO_TARGET_DIR", &format!("/tmp/playder_target_{}", test_name))
+            .args(&["run", "--"])
+            .args(args)
+            .output()
+            .expect("Failed to execute process");
+
+        output.status.success()
+            .then(|| output.stdout)
+            .ok_or_else(|| String::from_utf8_lossy(&output.stderr).to_string())
+    }
+
+    #[test]
+    fn test_multiple_outputs_mrt() {
+        // Two fragment outputs should each stream to their own `<name>.raw`
+        // file instead of competing for stdout.
+        let shader_source = r#"
+        #version 330 core
+        out vec4 testMrtColor;
+        out vec4 testMrtAux;
+        void main() {
+            testMrtColor = vec4(1.0, 0.0, 0.0, 1.0);
+            testMrtAux = vec4(0.0, 1.0, 0.0, 1.0);
+        }
+        "#;
+
+        let mut shader_path = env::temp_dir();
+        shader_path.push("temp_shader_test_multiple_outputs_mrt.frag");
+        let mut file = File::create(&shader_path).expect("Failed to create shader file");
+        file.write_all(shader_source.as_bytes()).expect("Failed to write shader source");
+        let shader_path_str = shader_path.to_str().unwrap().to_string();
+
+        let result = run_playder("test_multiple_outputs_mrt", &[
+            &shader_path_str, "1", "1", "1", "1",
+            "--outputs", "testMrtColor,testMrtAux",
+        ]);
+
+        std::fs::remove_file(&shader_path).expect("Failed to remove temporary shader file");
+        result.unwrap_or_else(|err| panic!("Test failed with error: {}", err));
+
+        let color = std::fs::read("testMrtColor.raw").expect("Failed to read testMrtColor.raw");
+        let aux = std::fs::read("testMrtAux.raw").expect("Failed to read testMrtAux.raw");
+        std::fs::remove_file("testMrtColor.raw").ok();
+        std::fs::remove_file("testMrtAux.raw").ok();
+
+        assert_eq!(color, vec![255, 0, 0]);
+        assert_eq!(aux, vec![0, 255, 0]);
+    }
+
+    #[test]
+    fn test_uniform_type_mismatch() {
+        // A `--uniform` value with the wrong component count for the
+        // shader's declared type should fail fast with a clear error
+        // instead of silently binding garbage.
+        let shader_source = r#"
+        #version 330 core
+        uniform float uScale;
+        out vec4 FragColor;
+        void main() {
+            FragColor = vec4(uScale, 0.0, 0.0, 1.0);
+        }
+        "#;
+
+        let mut shader_path = env::temp_dir();
+        shader_path.push("temp_shader_test_uniform_type_mismatch.frag");
+        let mut file = File::create(&shader_path).expect("Failed to create shader file");
+        file.write_all(shader_source.as_bytes()).expect("Failed to write shader source");
+        let shader_path_str = shader_path.to_str().unwrap().to_string();
+
+        let result = run_playder("test_uniform_type_mismatch", &[
+            &shader_path_str, "1", "1", "1", "1",
+            "--uniform", "uScale=1,2",
+        ]);
+
+        std::fs::remove_file(&shader_path).expect("Failed to remove temporary shader file");
+
+        match result {
+            Ok(_) => panic!("Expected a uniform type mismatch error, but rendering succeeded"),
+            Err(err) => assert!(
+                err.contains("Uniform 'uScale' expected 1 component(s), got 2."),
+                "Unexpected error message: {}", err
+            ),
+        }
+    }
+
+    #[test]
+    fn test_two_pass_chain() {
+        // A two-pass graph: "base" writes a constant color and "final"
+        // samples it via iChannel0 and transforms it, catching
+        // ordering/wiring regressions between passes.
+        let base_source = r#"
+        #version 330 core
+        out vec4 FragColor;
+        void main() {
+            FragColor = vec4(0.2, 0.0, 0.0, 1.0);
+        }
+        "#;
+        let final_source = r#"
+        #version 330 core
+        uniform sampler2D iChannel0;
+        out vec4 FragColor;
+        void main() {
+            vec4 base = texture(iChannel0, vec2(0.5, 0.5));
+            FragColor = vec4(1.0 - base.r, 0.0, 0.0, 1.0);
+        }
+        "#;
+
+        let mut base_path = env::temp_dir();
+        base_path.push("temp_shader_test_two_pass_feedback_base.frag");
+        File::create(&base_path).expect("Failed to create base shader file")
+            .write_all(base_source.as_bytes()).expect("Failed to write base shader source");
+
+        let mut final_path = env::temp_dir();
+        final_path.push("temp_shader_test_two_pass_feedback_final.frag");
+        File::create(&final_path).expect("Failed to create final shader file")
+            .write_all(final_source.as_bytes()).expect("Failed to write final shader source");
+
+        let manifest = format!(
+            r#"{{"passes": [{{"name": "base", "shader": {:?}}}, {{"name": "final", "shader": {:?}, "inputs": ["base"]}}], "final": "final"}}"#,
+            base_path.to_str().unwrap(), final_path.to_str().unwrap(),
+        );
+        let mut manifest_path = env::temp_dir();
+        manifest_path.push("temp_passes_test_two_pass_feedback.json");
+        File::create(&manifest_path).expect("Failed to create passes manifest file")
+            .write_all(manifest.as_bytes()).expect("Failed to write passes manifest");
+        let manifest_path_str = manifest_path.to_str().unwrap().to_string();
+
+        let result = run_playder("test_two_pass_chain", &[
+            "", "1", "1", "1", "1",
+            "--passes", &manifest_path_str,
+        ]);
+
+        std::fs::remove_file(&base_path).ok();
+        std::fs::remove_file(&final_path).ok();
+        std::fs::remove_file(&manifest_path).ok();
+
+        match result {
+            Ok(output) => assert_eq!(output, vec![204, 0, 0]),
+            Err(err) => panic!("Test failed with error: {}", err),
         }
     }
 }